@@ -2,10 +2,20 @@
 
 #[ink::contract]
 pub mod strategy_manager {
+    pub use access_control::Role;
     use ink::storage::Mapping;
     use ink::prelude::vec::Vec;
     use ink::prelude::string::String;
 
+    pub use strategy_types::{ParameterError, BASIS_POINTS, MAX_ALLOCATIONS, MAX_SLIPPAGE_BPS};
+
+    /// Asset a strategy allocates to
+    pub type Asset = strategy_types::Asset<AccountId>;
+    /// Target share of a strategy's funds for one asset
+    pub type Allocation = strategy_types::Allocation<AccountId>;
+    /// Typed strategy parameters, shared with `dynavest_strategy` and the backend
+    pub type StrategyParameters = strategy_types::StrategyParameters<AccountId>;
+
     /// Represents a DeFi strategy with its parameters and metadata
     #[derive(scale::Encode, scale::Decode, Clone, PartialEq, Eq)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -16,8 +26,8 @@ pub mod strategy_manager {
         pub name: String,
         /// Risk level from 1-10 (1 = lowest risk, 10 = highest risk)
         pub risk_level: u8,
-        /// JSON-encoded parameters for the strategy
-        pub parameters: String,
+        /// Allocation targets and trading limits for the strategy
+        pub parameters: StrategyParameters,
         /// Timestamp when the strategy was created
         pub created_at: u64,
         /// Whether the strategy is active
@@ -53,6 +63,24 @@ pub mod strategy_manager {
         pub strategy_id: u32,
     }
 
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        pub role: Role,
+        #[ink(topic)]
+        pub account: AccountId,
+        pub sender: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        pub role: Role,
+        #[ink(topic)]
+        pub account: AccountId,
+        pub sender: AccountId,
+    }
+
     /// Custom error types
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
@@ -65,10 +93,29 @@ pub mod strategy_manager {
         InvalidRiskLevel,
         /// Strategy name cannot be empty
         EmptyStrategyName,
-        /// Strategy parameters cannot be empty
+        /// Strategy parameters have no allocations
         EmptyParameters,
+        /// Allocations are zero or do not sum to 100%
+        InvalidAllocation,
+        /// The same asset appears in more than one allocation
+        DuplicateAsset,
+        /// Slippage or rebalance threshold out of range
+        InvalidParameters,
         /// Maximum number of strategies reached
         MaxStrategiesReached,
+        /// Caller does not hold the required role
+        MissingRole,
+    }
+
+    impl From<ParameterError> for Error {
+        fn from(error: ParameterError) -> Self {
+            match error {
+                ParameterError::NoAllocations => Error::EmptyParameters,
+                ParameterError::InvalidAllocation => Error::InvalidAllocation,
+                ParameterError::DuplicateAsset => Error::DuplicateAsset,
+                ParameterError::OutOfRange => Error::InvalidParameters,
+            }
+        }
     }
 
     /// The Strategy Manager contract storage
@@ -80,6 +127,8 @@ pub mod strategy_manager {
         next_strategy_id: u32,
         /// Maximum number of strategies per account
         max_strategies_per_account: u32,
+        /// Role memberships, keyed by (account, role)
+        roles: Mapping<(AccountId, Role), ()>,
     }
 
     /// Contract implementation
@@ -87,10 +136,13 @@ pub mod strategy_manager {
         /// Constructor that initializes the contract
         #[ink(constructor)]
         pub fn new() -> Self {
+            let mut roles = Mapping::new();
+            roles.insert((Self::env().caller(), Role::Admin), &());
             Self {
                 strategies: Mapping::new(),
                 next_strategy_id: 1,
                 max_strategies_per_account: 100, // Reasonable limit
+                roles,
             }
         }
 
@@ -100,7 +152,7 @@ pub mod strategy_manager {
             &mut self,
             name: String,
             risk_level: u8,
-            parameters: String,
+            parameters: StrategyParameters,
         ) -> Result<u32, Error> {
             let caller = self.env().caller();
             
@@ -109,9 +161,7 @@ pub mod strategy_manager {
                 return Err(Error::EmptyStrategyName);
             }
             
-            if parameters.is_empty() {
-                return Err(Error::EmptyParameters);
-            }
+            parameters.validate()?;
             
             if risk_level < 1 || risk_level > 10 {
                 return Err(Error::InvalidRiskLevel);
@@ -179,7 +229,7 @@ pub mod strategy_manager {
             strategy_id: u32,
             name: String,
             risk_level: u8,
-            parameters: String,
+            parameters: StrategyParameters,
         ) -> Result<(), Error> {
             let caller = self.env().caller();
             
@@ -188,9 +238,7 @@ pub mod strategy_manager {
                 return Err(Error::EmptyStrategyName);
             }
             
-            if parameters.is_empty() {
-                return Err(Error::EmptyParameters);
-            }
+            parameters.validate()?;
             
             if risk_level < 1 || risk_level > 10 {
                 return Err(Error::InvalidRiskLevel);
@@ -296,6 +344,77 @@ pub mod strategy_manager {
         pub fn get_next_strategy_id(&self) -> u32 {
             self.next_strategy_id
         }
+
+        /// Update the maximum number of strategies per account (only admin)
+        #[ink(message)]
+        pub fn set_max_strategies_per_account(&mut self, max: u32) -> Result<(), Error> {
+            if !self.has_role(Role::Admin, self.env().caller()) {
+                return Err(Error::MissingRole);
+            }
+            self.max_strategies_per_account = max;
+            Ok(())
+        }
+
+        /// Check whether an account holds a role
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            self.roles.contains((account, role))
+        }
+
+        /// Grant a role to an account (only admin)
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if !self.has_role(role.admin_role(), caller) {
+                return Err(Error::MissingRole);
+            }
+
+            if self.roles.insert((account, role), &()).is_none() {
+                self.env().emit_event(RoleGranted {
+                    role,
+                    account,
+                    sender: caller,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Revoke a role from an account (only admin)
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if !self.has_role(role.admin_role(), caller) {
+                return Err(Error::MissingRole);
+            }
+
+            self.remove_role(role, account, caller);
+            Ok(())
+        }
+
+        /// Give up a role held by the caller
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if !self.has_role(role, caller) {
+                return Err(Error::MissingRole);
+            }
+
+            self.remove_role(role, caller, caller);
+            Ok(())
+        }
+
+        /// Remove a role membership and emit `RoleRevoked` if it existed
+        fn remove_role(&mut self, role: Role, account: AccountId, sender: AccountId) {
+            if self.roles.contains((account, role)) {
+                self.roles.remove((account, role));
+                self.env().emit_event(RoleRevoked {
+                    role,
+                    account,
+                    sender,
+                });
+            }
+        }
     }
 
     /// Unit tests
@@ -303,6 +422,16 @@ pub mod strategy_manager {
     mod tests {
         use super::*;
 
+        /// Everything in the native token
+        fn params() -> StrategyParameters {
+            StrategyParameters {
+                allocations: vec![Allocation { asset: Asset::Native, target_bps: 10_000 }],
+                max_slippage_bps: 100,
+                rebalance_threshold_bps: 500,
+                auto_compound: false,
+            }
+        }
+
         #[ink::test]
         fn new_works() {
             let contract = StrategyManager::new();
@@ -316,7 +445,7 @@ pub mod strategy_manager {
             let result = contract.save_strategy(
                 "Test Strategy".to_string(),
                 5,
-                "{}".to_string(),
+                params(),
             );
             assert!(result.is_ok());
             assert_eq!(result.unwrap(), 1);
@@ -331,7 +460,7 @@ pub mod strategy_manager {
             contract.save_strategy(
                 "Test Strategy".to_string(),
                 5,
-                "{}".to_string(),
+                params(),
             ).unwrap();
             
             // Get strategies
@@ -347,7 +476,7 @@ pub mod strategy_manager {
             let result = contract.save_strategy(
                 "Test Strategy".to_string(),
                 11, // Invalid risk level
-                "{}".to_string(),
+                params(),
             );
             assert_eq!(result, Err(Error::InvalidRiskLevel));
         }
@@ -358,7 +487,7 @@ pub mod strategy_manager {
             let result = contract.save_strategy(
                 "".to_string(), // Empty name
                 5,
-                "{}".to_string(),
+                params(),
             );
             assert_eq!(result, Err(Error::EmptyStrategyName));
         }
@@ -371,7 +500,7 @@ pub mod strategy_manager {
             let strategy_id = contract.save_strategy(
                 "Original Strategy".to_string(),
                 5,
-                "{}".to_string(),
+                params(),
             ).unwrap();
             
             // Update the strategy
@@ -379,7 +508,7 @@ pub mod strategy_manager {
                 strategy_id,
                 "Updated Strategy".to_string(),
                 8,
-                "{\"updated\": true}".to_string(),
+                StrategyParameters { auto_compound: true, ..params() },
             );
             assert!(result.is_ok());
             
@@ -387,6 +516,60 @@ pub mod strategy_manager {
             let strategy = contract.get_strategy(strategy_id).unwrap();
             assert_eq!(strategy.name, "Updated Strategy");
             assert_eq!(strategy.risk_level, 8);
+            assert!(strategy.parameters.auto_compound);
+        }
+
+        #[ink::test]
+        fn invalid_parameters_fail() {
+            let mut contract = StrategyManager::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let token = Asset::Psp22(accounts.django);
+            let with_allocations = |allocations: Vec<(Asset, u16)>| StrategyParameters {
+                allocations: allocations
+                    .into_iter()
+                    .map(|(asset, target_bps)| Allocation { asset, target_bps })
+                    .collect(),
+                ..params()
+            };
+
+            let save = |contract: &mut StrategyManager, parameters| {
+                contract.save_strategy("Test Strategy".to_string(), 5, parameters)
+            };
+            assert_eq!(save(&mut contract, with_allocations(vec![])), Err(Error::EmptyParameters));
+            assert_eq!(
+                save(&mut contract, with_allocations(vec![(Asset::Native, 5_000), (token, 4_000)])),
+                Err(Error::InvalidAllocation)
+            );
+            assert_eq!(
+                save(&mut contract, with_allocations(vec![(token, 5_000), (token, 5_000)])),
+                Err(Error::DuplicateAsset)
+            );
+            assert_eq!(
+                save(&mut contract, StrategyParameters { max_slippage_bps: MAX_SLIPPAGE_BPS + 1, ..params() }),
+                Err(Error::InvalidParameters)
+            );
+            assert!(save(&mut contract, with_allocations(vec![(Asset::Native, 5_000), (token, 5_000)])).is_ok());
+        }
+
+        #[ink::test]
+        fn role_management_works() {
+            let mut contract = StrategyManager::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            assert!(contract.has_role(Role::Admin, accounts.alice));
+
+            // Non-admins cannot change limits or hand out roles
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_max_strategies_per_account(5), Err(Error::MissingRole));
+            assert_eq!(contract.grant_role(Role::Admin, accounts.bob), Err(Error::MissingRole));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert!(contract.grant_role(Role::Admin, accounts.bob).is_ok());
+            assert!(contract.renounce_role(Role::Admin).is_ok());
+            assert!(!contract.has_role(Role::Admin, accounts.alice));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert!(contract.set_max_strategies_per_account(5).is_ok());
+            assert_eq!(contract.get_max_strategies_per_account(), 5);
         }
 
         #[ink::test]
@@ -397,7 +580,7 @@ pub mod strategy_manager {
             let strategy_id = contract.save_strategy(
                 "Test Strategy".to_string(),
                 5,
-                "{}".to_string(),
+                params(),
             ).unwrap();
             
             // Delete the strategy
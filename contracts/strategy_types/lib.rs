@@ -0,0 +1,189 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Strategy parameter types shared by the DynaVest contracts.
+//!
+//! Like `access_control`, the crate only depends on SCALE codec so that
+//! contracts built against different ink! versions encode parameters the
+//! same way. The types are generic over the account type: `strategy_manager`
+//! uses ink! 4's 32-byte `AccountId`, `dynavest_strategy` ink! 6's 20-byte
+//! `Address`. The `ink-v6-layout` feature adds the `StorageLayout` impls
+//! ink! 6 needs to keep the types in contract storage.
+
+extern crate alloc;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Basis points in 100%
+pub const BASIS_POINTS: u32 = 10_000;
+/// Maximum number of allocations in a strategy's parameters
+pub const MAX_ALLOCATIONS: usize = 16;
+/// Upper bound for a strategy's maximum slippage (10%)
+pub const MAX_SLIPPAGE_BPS: u16 = 1_000;
+
+/// Asset a strategy holds or allocates to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub enum Asset<AccountId> {
+    /// The chain's native token
+    Native,
+    /// A PSP22 token contract
+    Psp22(AccountId),
+}
+
+/// Target share of a strategy's funds for one asset
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct Allocation<AccountId> {
+    /// Asset being allocated to
+    pub asset: Asset<AccountId>,
+    /// Target share in basis points
+    pub target_bps: u16,
+}
+
+/// Typed strategy parameters, mirrored by `polkadot::StrategyParameters` in the backend
+#[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+pub struct StrategyParameters<AccountId> {
+    /// Target allocations, summing to 100%
+    pub allocations: Vec<Allocation<AccountId>>,
+    /// Maximum slippage tolerated when trading, in basis points
+    pub max_slippage_bps: u16,
+    /// Drift from the targets that triggers a rebalance, in basis points
+    pub rebalance_threshold_bps: u16,
+    /// Whether rewards are reinvested
+    pub auto_compound: bool,
+}
+
+/// Why a set of strategy parameters was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterError {
+    /// No allocations were given
+    NoAllocations,
+    /// Too many allocations, a zero target, or targets not summing to 100%
+    InvalidAllocation,
+    /// The same asset appears in more than one allocation
+    DuplicateAsset,
+    /// Slippage or rebalance threshold out of range
+    OutOfRange,
+}
+
+impl<AccountId> Default for StrategyParameters<AccountId> {
+    /// Everything in the native token
+    fn default() -> Self {
+        Self {
+            allocations: vec![Allocation { asset: Asset::Native, target_bps: BASIS_POINTS as u16 }],
+            max_slippage_bps: 100,
+            rebalance_threshold_bps: 500,
+            auto_compound: false,
+        }
+    }
+}
+
+impl<AccountId: PartialEq> StrategyParameters<AccountId> {
+    /// Check allocations sum to 100% over distinct assets and limits are in range
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        if self.allocations.is_empty() {
+            return Err(ParameterError::NoAllocations);
+        }
+        if self.allocations.len() > MAX_ALLOCATIONS {
+            return Err(ParameterError::InvalidAllocation);
+        }
+        let mut total_bps: u32 = 0;
+        for (index, allocation) in self.allocations.iter().enumerate() {
+            if allocation.target_bps == 0 {
+                return Err(ParameterError::InvalidAllocation);
+            }
+            if self.allocations[..index].iter().any(|other| other.asset == allocation.asset) {
+                return Err(ParameterError::DuplicateAsset);
+            }
+            total_bps += allocation.target_bps as u32;
+        }
+        if total_bps != BASIS_POINTS {
+            return Err(ParameterError::InvalidAllocation);
+        }
+        if self.max_slippage_bps > MAX_SLIPPAGE_BPS || self.rebalance_threshold_bps as u32 > BASIS_POINTS {
+            return Err(ParameterError::OutOfRange);
+        }
+        Ok(())
+    }
+
+    /// Target share of `asset`, in basis points
+    pub fn target_bps(&self, asset: &Asset<AccountId>) -> u16 {
+        self.allocations
+            .iter()
+            .find(|allocation| &allocation.asset == asset)
+            .map_or(0, |allocation| allocation.target_bps)
+    }
+}
+
+/// Lay a type out as a single packed cell, the same as ink!'s derive does for
+/// `Packed` types
+macro_rules! leaf_layout {
+    ($($ty:ident),*) => {
+        $(
+            #[cfg(feature = "ink-v6-layout")]
+            impl<AccountId: scale_info::TypeInfo + 'static> ink_storage_traits_v6::StorageLayout for $ty<AccountId> {
+                fn layout(key: &ink_primitives_v6::Key) -> ink_metadata_v6::layout::Layout {
+                    use ink_metadata_v6::layout::{Layout, LayoutKey, LeafLayout};
+                    Layout::Leaf(LeafLayout::from_key::<Self>(LayoutKey::from(key)))
+                }
+            }
+        )*
+    };
+}
+
+leaf_layout!(Asset, Allocation, StrategyParameters);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scale::{Decode, Encode};
+
+    type Params = StrategyParameters<[u8; 20]>;
+
+    fn with_allocations(allocations: Vec<(Asset<[u8; 20]>, u16)>) -> Params {
+        Params {
+            allocations: allocations
+                .into_iter()
+                .map(|(asset, target_bps)| Allocation { asset, target_bps })
+                .collect(),
+            ..Params::default()
+        }
+    }
+
+    #[test]
+    fn default_is_valid() {
+        assert_eq!(Params::default().validate(), Ok(()));
+        assert_eq!(Params::default().target_bps(&Asset::Native), 10_000);
+    }
+
+    #[test]
+    fn validate_rejects_bad_allocations() {
+        let token = Asset::Psp22([7; 20]);
+        assert_eq!(with_allocations(vec![]).validate(), Err(ParameterError::NoAllocations));
+        assert_eq!(
+            with_allocations(vec![(Asset::Native, 5_000), (token, 4_000)]).validate(),
+            Err(ParameterError::InvalidAllocation)
+        );
+        assert_eq!(
+            with_allocations(vec![(token, 5_000), (token, 5_000)]).validate(),
+            Err(ParameterError::DuplicateAsset)
+        );
+        assert_eq!(
+            Params { max_slippage_bps: MAX_SLIPPAGE_BPS + 1, ..Params::default() }.validate(),
+            Err(ParameterError::OutOfRange)
+        );
+        assert_eq!(with_allocations(vec![(Asset::Native, 5_000), (token, 5_000)]).validate(), Ok(()));
+    }
+
+    #[test]
+    fn encoding_is_stable() {
+        // The backend and frontend encode assets by these indices
+        assert_eq!(Asset::<[u8; 20]>::Native.encode(), vec![0]);
+        let mut psp22 = vec![1];
+        psp22.extend([7; 20]);
+        assert_eq!(Asset::Psp22([7u8; 20]).encode(), psp22);
+        assert_eq!(Asset::<[u8; 20]>::decode(&mut &psp22[..]).unwrap(), Asset::Psp22([7; 20]));
+    }
+}
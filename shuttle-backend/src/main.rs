@@ -24,7 +24,7 @@ mod chat;
 use chat::{ChatService, ChatRequest, ChatResponse};
 
 mod polkadot;
-use polkadot::{PolkadotClient, StrategyParameters as PolkadotStrategyParameters};
+use polkadot::{PolkadotClient, StrategyParameters as PolkadotStrategyParameters, StrategyPerformance};
 
 mod polkadot_defi_knowledge;
 use polkadot_defi_knowledge::{get_polkadot_protocols, get_polkadot_strategy_recommendation, search_polkadot_protocols};
@@ -35,6 +35,9 @@ use defi_service::{DefiService, DefiInfoRequest, DefiResponse, CryptoPriceData};
 mod contract_service;
 use contract_service::{ContractService, CreateStrategyParams, InvestmentParams, WithdrawParams, ContractStrategy};
 
+mod relayer;
+use relayer::{RelayerService, SignedIntent};
+
 mod rag_system;
 use rag_system::{RAGSystem, SearchRequest, SearchResult, EmbeddingRequest};
 
@@ -203,10 +206,10 @@ struct AppState {
     contract_config: ContractConfig,
     hyperbridge_client: HyperbridgeClient,
     chat_service: std::sync::Arc<ChatService>,
-    #[allow(dead_code)]
     polkadot_client: std::sync::Arc<PolkadotClient>,
     defi_service: std::sync::Arc<DefiService>,
     contract_service: std::sync::Arc<ContractService>,
+    relayer: std::sync::Arc<RelayerService>,
     rag_system: std::sync::Arc<RAGSystem>,
 }
 
@@ -833,6 +836,32 @@ async fn invest_in_contract_strategy(
     }
 }
 
+async fn relay_contract_intent(
+    State(state): State<AppState>,
+    Json(request): Json<SignedIntent>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    info!("Relaying intent for contract strategy: {}", request.intent.strategy_id);
+
+    // Submit the intent, paying the fees for the signer
+    match state.relayer.submit_intent(request).await {
+        Ok(tx_hash) => {
+            Ok(Json(ApiResponse {
+                success: true,
+                data: Some(tx_hash),
+                error: None,
+            }))
+        }
+        Err(e) => {
+            info!("Failed to relay contract intent: {}", e);
+            Ok(Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            }))
+        }
+    }
+}
+
 async fn get_contract_strategies(
     State(state): State<AppState>,
     Path(user_address): Path<String>,
@@ -854,6 +883,31 @@ async fn get_contract_strategies(
     }
 }
 
+async fn get_contract_strategy_performance(
+    State(state): State<AppState>,
+    Path(strategy_id): Path<u32>,
+) -> Result<Json<ApiResponse<StrategyPerformance>>, StatusCode> {
+    info!("Getting performance for contract strategy: {}", strategy_id);
+
+    // Built from the strategy's `StrategyReported` keeper reports
+    match state.polkadot_client.get_strategy_performance(strategy_id).await {
+        Ok(performance) => {
+            Ok(Json(ApiResponse {
+                success: true,
+                data: Some(performance),
+                error: None,
+            }))
+        }
+        Err(e) => {
+            Ok(Json(ApiResponse {
+                success: false,
+                data: None,
+                error: Some(e.to_string()),
+            }))
+        }
+    }
+}
+
 async fn withdraw_from_contract_strategy(
     State(state): State<AppState>,
     Json(request): Json<WithdrawParams>,
@@ -1186,6 +1240,7 @@ async fn main(
         polkadot_client,
         defi_service,
         contract_service,
+        relayer: std::sync::Arc::new(RelayerService::new()),
         rag_system,
     };
 
@@ -1212,7 +1267,9 @@ async fn main(
         .route("/contract/strategy", post(create_contract_strategy))
         .route("/contract/invest", post(invest_in_contract_strategy))
         .route("/contract/withdraw", post(withdraw_from_contract_strategy))
+        .route("/contract/intent", post(relay_contract_intent))
         .route("/contract/strategies/{user_address}", get(get_contract_strategies))
+        .route("/contract/performance/{strategy_id}", get(get_contract_strategy_performance))
         // RAG and semantic search
         .route("/rag/search", post(semantic_search))
         .route("/rag/query", post(rag_query))
@@ -1248,6 +1305,7 @@ async fn main(
     info!("  POST   /contract/strategy - Create strategy on ink! contract");
     info!("  POST   /contract/invest - Invest in ink! contract strategy");
     info!("  POST   /contract/withdraw - Withdraw from ink! contract strategy");
+    info!("  POST   /contract/intent - Relay a signed deposit or withdrawal intent");
     info!("  GET    /contract/strategies/:user_address - Get user's contract strategies");
     info!("  POST   /rag/search - Semantic search through knowledge base");
     info!("  POST   /rag/query - RAG-powered AI query with context");
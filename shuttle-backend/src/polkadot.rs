@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::info;
+use subxt::ext::codec::{Decode, Encode};
+use subxt::utils::H160;
 use subxt::{OnlineClient, PolkadotConfig as SubxtPolkadotConfig};
 
 // Polkadot configuration
@@ -34,20 +36,30 @@ pub struct PolkadotStrategy {
     pub updated_at: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Mirrors the on-chain `StrategyParameters` shared by the contracts through
+// contracts/strategy_types; SCALE encoding matches the contract byte for byte
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Encode, Decode)]
+#[codec(crate = subxt::ext::codec)]
 pub struct StrategyParameters {
-    pub tokens: Vec<Token>,
-    pub allocation: Vec<u8>, // Percentage allocation
-    pub max_slippage: u8,
-    pub rebalance_threshold: u8,
+    pub allocations: Vec<Allocation>,
+    pub max_slippage_bps: u16,
+    pub rebalance_threshold_bps: u16,
     pub auto_compound: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Token {
-    pub symbol: String,
-    pub contract_address: String,
-    pub decimals: u8,
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Encode, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub struct Allocation {
+    pub asset: Asset,
+    pub target_bps: u16, // Basis points, 10_000 = 100%
+}
+
+// Mirrors `strategy_types::Asset<Address>`; JSON is `"Native"` or `{"Psp22": "0x..."}`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Encode, Decode)]
+#[codec(crate = subxt::ext::codec)]
+pub enum Asset {
+    Native,
+    Psp22(H160), // 20-byte PSP22 contract address
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -83,6 +95,48 @@ pub struct StrategyPerformance {
     pub last_updated: u64,
 }
 
+// Mirrors the `StrategyReported` event emitted by the dynavest_strategy contract
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StrategyReport {
+    pub strategy_id: u32,
+    pub adapter: H160, // Adapter whose deployed funds produced the result
+    pub gain: u128,
+    pub loss: u128,
+    pub balance: u128,
+    pub price_per_share: u128, // Scaled by PRICE_PRECISION
+    pub timestamp: u64,        // Milliseconds
+}
+
+impl StrategyPerformance {
+    // Build performance from a strategy's keeper reports, oldest first
+    pub fn from_reports(strategy_id: u32, reports: &[StrategyReport]) -> Option<Self> {
+        let first = reports.first()?;
+        let last = reports.last()?;
+        let pnl = reports
+            .iter()
+            .map(|report| report.gain as i128 - report.loss as i128)
+            .sum();
+
+        // Annualize the share price growth between the first and last report
+        let elapsed_ms = last.timestamp.saturating_sub(first.timestamp);
+        let apy = if elapsed_ms == 0 || first.price_per_share == 0 {
+            0.0
+        } else {
+            let growth = last.price_per_share as f64 / first.price_per_share as f64;
+            let years = elapsed_ms as f64 / MILLISECONDS_PER_YEAR as f64;
+            (growth.powf(1.0 / years) - 1.0) * 100.0
+        };
+
+        Some(Self {
+            strategy_id,
+            total_value: last.balance,
+            pnl,
+            apy,
+            last_updated: last.timestamp / 1000,
+        })
+    }
+}
+
 // Polkadot client wrapper
 pub struct PolkadotClient {
     #[allow(dead_code)]
@@ -153,17 +207,46 @@ impl PolkadotClient {
     ) -> Result<StrategyPerformance, Box<dyn std::error::Error>> {
         info!("Fetching performance for strategy: {}", strategy_id);
         
-        // TODO: Implement actual contract query
-        // For now, return mock performance data
-        let performance = StrategyPerformance {
-            strategy_id,
-            total_value: 1000000000000, // 1000 DOT (12 decimals)
-            pnl: 50000000000,           // 50 DOT profit
-            apy: 12.5,
-            last_updated: chrono::Utc::now().timestamp() as u64,
-        };
+        let reports = self.get_strategy_reports(strategy_id).await?;
+        StrategyPerformance::from_reports(strategy_id, &reports)
+            .ok_or_else(|| format!("No keeper reports for strategy {}", strategy_id).into())
+    }
+
+    // `StrategyReported` events for a strategy, oldest first
+    pub async fn get_strategy_reports(
+        &self,
+        strategy_id: u32,
+    ) -> Result<Vec<StrategyReport>, Box<dyn std::error::Error>> {
+        info!("Fetching keeper reports for strategy: {}", strategy_id);
+        
+        if !self.is_mock {
+            // TODO: Decode `StrategyReported` events of the strategy contract
+            return Err("Reading contract events is not implemented yet".into());
+        }
         
-        Ok(performance)
+        // Mock history: 1000 DOT of shares, reported every 15 days
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let interval_ms = 15 * 86_400_000;
+        let results: [(u128, u128, u128); 3] = [
+            (0, 0, 1_000_000_000_000_000),
+            (30_000_000_000_000, 0, 1_030_000_000_000_000),
+            (25_000_000_000_000, 5_000_000_000_000, 1_050_000_000_000_000),
+        ];
+        let reports = results
+            .iter()
+            .enumerate()
+            .map(|(i, &(gain, loss, balance))| StrategyReport {
+                strategy_id,
+                adapter: MOCK_ADAPTER,
+                gain,
+                loss,
+                balance,
+                price_per_share: balance / 1_000, // balance * PRICE_PRECISION / shares
+                timestamp: now_ms - (results.len() - 1 - i) as u64 * interval_ms,
+            })
+            .collect();
+        
+        Ok(reports)
     }
 
     pub async fn get_strategy_details(
@@ -180,21 +263,18 @@ impl PolkadotClient {
             name: "DeFi Yield Strategy".to_string(),
             risk_level: 5,
             parameters: StrategyParameters {
-                tokens: vec![
-                    Token {
-                        symbol: "DOT".to_string(),
-                        contract_address: "native".to_string(),
-                        decimals: 12,
+                allocations: vec![
+                    Allocation {
+                        asset: Asset::Native,
+                        target_bps: 6_000,
                     },
-                    Token {
-                        symbol: "USDT".to_string(),
-                        contract_address: "0x1234...".to_string(),
-                        decimals: 6,
+                    Allocation {
+                        asset: Asset::Psp22(MOCK_USDT),
+                        target_bps: 4_000,
                     },
                 ],
-                allocation: vec![60, 40],
-                max_slippage: 1,
-                rebalance_threshold: 5,
+                max_slippage_bps: 100,
+                rebalance_threshold_bps: 500,
                 auto_compound: true,
             },
             status: StrategyStatus::Active,
@@ -253,16 +333,14 @@ impl PolkadotClient {
                 name: "Conservative DeFi".to_string(),
                 risk_level: 3,
                 parameters: StrategyParameters {
-                    tokens: vec![
-                        Token {
-                            symbol: "DOT".to_string(),
-                            contract_address: "native".to_string(),
-                            decimals: 12,
+                    allocations: vec![
+                        Allocation {
+                            asset: Asset::Native,
+                            target_bps: 10_000,
                         },
                     ],
-                    allocation: vec![100],
-                    max_slippage: 1,
-                    rebalance_threshold: 10,
+                    max_slippage_bps: 100,
+                    rebalance_threshold_bps: 1_000,
                     auto_compound: true,
                 },
                 status: StrategyStatus::Active,
@@ -309,39 +387,64 @@ impl PolkadotClient {
     }
 }
 
+// Placeholder USDT contract used by the mock responses
+const MOCK_USDT: H160 = H160([0x12; 20]);
+// Placeholder adapter contract used by the mock keeper reports
+const MOCK_ADAPTER: H160 = H160([0x34; 20]);
+
+// Limits shared with the on-chain `StrategyParameters::validate`
+pub const BASIS_POINTS: u32 = 10_000;
+pub const MAX_ALLOCATIONS: usize = 16;
+pub const MAX_SLIPPAGE_BPS: u16 = 1_000;
+pub const MILLISECONDS_PER_YEAR: u64 = 31_536_000_000;
+
 // Helper functions for strategy management
 #[allow(dead_code)]
 pub fn validate_strategy_parameters(params: &StrategyParameters) -> Result<(), String> {
-    // Validate allocations sum to 100%
-    let total_allocation: u8 = params.allocation.iter().sum();
-    if total_allocation != 100 {
-        return Err(format!("Allocation must sum to 100%, got {}", total_allocation));
+    // Same rules the contract enforces in `StrategyParameters::validate`
+    if params.allocations.is_empty() || params.allocations.len() > MAX_ALLOCATIONS {
+        return Err(format!("Strategy must have 1 to {} allocations", MAX_ALLOCATIONS));
+    }
+    
+    // Validate each asset appears once with a non-zero target
+    for (i, allocation) in params.allocations.iter().enumerate() {
+        if allocation.target_bps == 0 {
+            return Err(format!("Allocation for {:?} must be non-zero", allocation.asset));
+        }
+        if params.allocations[..i].iter().any(|other| other.asset == allocation.asset) {
+            return Err(format!("Asset {:?} is allocated more than once", allocation.asset));
+        }
     }
     
-    // Validate token count matches allocation count
-    if params.tokens.len() != params.allocation.len() {
-        return Err("Token count must match allocation count".to_string());
+    // Validate allocations sum to 100%
+    let total_bps: u32 = params.allocations.iter().map(|a| a.target_bps as u32).sum();
+    if total_bps != BASIS_POINTS {
+        return Err(format!("Allocation must sum to 10000 bps, got {}", total_bps));
     }
     
     // Validate slippage is reasonable
-    if params.max_slippage > 10 {
+    if params.max_slippage_bps > MAX_SLIPPAGE_BPS {
         return Err("Maximum slippage cannot exceed 10%".to_string());
     }
     
+    if params.rebalance_threshold_bps as u32 > BASIS_POINTS {
+        return Err("Rebalance threshold cannot exceed 100%".to_string());
+    }
+    
     Ok(())
 }
 
 #[allow(dead_code)]
 pub fn calculate_expected_return(
     params: &StrategyParameters,
-    market_data: &HashMap<String, f64>,
+    market_data: &HashMap<Asset, f64>,
 ) -> Result<f64, String> {
     let mut expected_return = 0.0;
     
-    for (i, token) in params.tokens.iter().enumerate() {
-        if let Some(apy) = market_data.get(&token.symbol) {
-            let allocation = params.allocation[i] as f64 / 100.0;
-            expected_return += apy * allocation;
+    for allocation in &params.allocations {
+        if let Some(apy) = market_data.get(&allocation.asset) {
+            let weight = allocation.target_bps as f64 / BASIS_POINTS as f64;
+            expected_return += apy * weight;
         }
     }
     
@@ -361,21 +464,18 @@ mod tests {
     #[test]
     fn test_validate_strategy_parameters() {
         let valid_params = StrategyParameters {
-            tokens: vec![
-                Token {
-                    symbol: "DOT".to_string(),
-                    contract_address: "native".to_string(),
-                    decimals: 12,
+            allocations: vec![
+                Allocation {
+                    asset: Asset::Native,
+                    target_bps: 6_000,
                 },
-                Token {
-                    symbol: "USDT".to_string(),
-                    contract_address: "0x1234".to_string(),
-                    decimals: 6,
+                Allocation {
+                    asset: Asset::Psp22(MOCK_USDT),
+                    target_bps: 4_000,
                 },
             ],
-            allocation: vec![60, 40],
-            max_slippage: 1,
-            rebalance_threshold: 5,
+            max_slippage_bps: 100,
+            rebalance_threshold_bps: 500,
             auto_compound: true,
         };
         
@@ -385,22 +485,92 @@ mod tests {
     #[test]
     fn test_invalid_allocation_sum() {
         let invalid_params = StrategyParameters {
-            tokens: vec![
-                Token {
-                    symbol: "DOT".to_string(),
-                    contract_address: "native".to_string(),
-                    decimals: 12,
+            allocations: vec![
+                Allocation {
+                    asset: Asset::Native,
+                    target_bps: 9_000, // Should be 10_000
                 },
             ],
-            allocation: vec![90], // Should be 100
-            max_slippage: 1,
-            rebalance_threshold: 5,
+            max_slippage_bps: 100,
+            rebalance_threshold_bps: 500,
             auto_compound: true,
         };
         
         assert!(validate_strategy_parameters(&invalid_params).is_err());
     }
 
+    #[test]
+    fn test_duplicate_asset_allocation() {
+        let duplicate_params = StrategyParameters {
+            allocations: vec![
+                Allocation {
+                    asset: Asset::Native,
+                    target_bps: 5_000,
+                },
+                Allocation {
+                    asset: Asset::Native,
+                    target_bps: 5_000,
+                },
+            ],
+            max_slippage_bps: 100,
+            rebalance_threshold_bps: 500,
+            auto_compound: true,
+        };
+        
+        assert!(validate_strategy_parameters(&duplicate_params).is_err());
+    }
+
+    #[test]
+    fn test_asset_encoding_matches_contract() {
+        // SCALE: variant index, then the 20-byte contract address
+        assert_eq!(Asset::Native.encode(), vec![0]);
+        let mut psp22 = vec![1];
+        psp22.extend([0x12; 20]);
+        assert_eq!(Asset::Psp22(MOCK_USDT).encode(), psp22);
+        assert_eq!(Asset::decode(&mut &psp22[..]).unwrap(), Asset::Psp22(MOCK_USDT));
+
+        // JSON: the shape declared in src/types/strategies.ts
+        assert_eq!(serde_json::to_string(&Asset::Native).unwrap(), r#""Native""#);
+        assert_eq!(
+            serde_json::to_string(&Asset::Psp22(MOCK_USDT)).unwrap(),
+            format!(r#"{{"Psp22":"0x{}"}}"#, "12".repeat(20))
+        );
+    }
+
+    #[test]
+    fn test_performance_from_reports() {
+        let report = |gain: u128, loss: u128, price_per_share: u128, timestamp: u64| StrategyReport {
+            strategy_id: 1,
+            adapter: H160::repeat_byte(0xad),
+            gain,
+            loss,
+            balance: price_per_share * 1_000,
+            price_per_share,
+            timestamp,
+        };
+        let reports = vec![
+            report(0, 0, 1_000_000_000_000, 0),
+            report(150, 0, 1_150_000_000_000, MILLISECONDS_PER_YEAR / 2),
+            report(0, 50, 1_100_000_000_000, MILLISECONDS_PER_YEAR),
+        ];
+
+        let performance = StrategyPerformance::from_reports(1, &reports).unwrap();
+        assert_eq!(performance.pnl, 100);
+        assert_eq!(performance.total_value, 1_100_000_000_000_000);
+        assert!((performance.apy - 10.0).abs() < 1e-9);
+        assert!(StrategyPerformance::from_reports(1, &[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_performance_comes_from_reports() {
+        let client = PolkadotClient::new_mock().await.unwrap();
+        let reports = client.get_strategy_reports(1).await.unwrap();
+        let performance = client.get_strategy_performance(1).await.unwrap();
+        assert_eq!(performance.pnl, 50_000_000_000_000);
+        assert_eq!(performance.total_value, reports.last().unwrap().balance);
+        assert!(performance.apy > 0.0);
+    }
+
     #[test]
     fn test_format_dot_amount() {
         assert_eq!(format_dot_amount(1_000_000_000_000), "1.0000 DOT");
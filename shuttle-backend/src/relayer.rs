@@ -0,0 +1,262 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tracing::info;
+
+// Mirrors `dynavest_strategy::IntentAction`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum IntentAction {
+    Invest,
+    Withdraw,
+}
+
+// Mirrors `dynavest_strategy::Intent`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Intent {
+    pub action: IntentAction,
+    pub strategy_id: u32,
+    pub amount: u128,
+    pub nonce: u64,
+    /// Milliseconds since the Unix epoch, compared against the block timestamp
+    pub deadline: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedIntent {
+    /// Account the intent acts for as 0x-prefixed hex: the H160 address, i.e. the last
+    /// 20 bytes of the Keccak-256 hash of the signer's uncompressed public key
+    pub signer: String,
+    pub intent: Intent,
+    /// Hex-encoded 65-byte recoverable ECDSA signature of the contract's `get_intent_hash`
+    pub signature: String,
+}
+
+/// Submits signed intents to `dynavest_strategy::execute_intent`, paying the fees
+/// so users without native balance can deposit and withdraw
+#[derive(Default)]
+pub struct RelayerService {
+    // Signers with an intent being submitted, so the same nonce is not paid for twice
+    in_flight: Mutex<HashSet<[u8; 20]>>,
+    // Mock contract storage for offline mode: the contract's next nonce per signer
+    mock_intent_nonces: Mutex<HashMap<[u8; 20], u64>>,
+}
+
+impl RelayerService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn submit_intent(&self, signed: SignedIntent) -> Result<String> {
+        info!(
+            "Relaying {:?} intent for strategy {} from {}",
+            signed.intent.action, signed.intent.strategy_id, signed.signer
+        );
+
+        let (signer, signature) =
+            Self::validate_signed_intent(&signed, chrono::Utc::now().timestamp_millis() as u64)?;
+
+        self.begin_relay(signer)?;
+        let result = self.relay(signer, &signed.intent, signature).await;
+        self.end_relay(signer);
+        let tx_hash = result?;
+        info!("Intent transaction hash: {}", tx_hash);
+
+        Ok(tx_hash)
+    }
+
+    async fn relay(&self, signer: [u8; 20], intent: &Intent, signature: [u8; 65]) -> Result<String> {
+        // The contract only runs a signer's intents in nonce order, so any other nonce would
+        // fail on chain after the relayer had paid for it
+        let next_nonce = self.get_intent_nonce(signer).await?;
+        if intent.nonce != next_nonce {
+            return Err(anyhow::anyhow!(
+                "Intent nonce {} is not the signer's next nonce {}",
+                intent.nonce, next_nonce
+            ));
+        }
+
+        // For now, we'll return a mock transaction hash
+        // In a real implementation, this would:
+        // 1. Dry-run execute_intent to catch a bad signature before paying fees
+        // 2. Sign the call with the relayer's funded key, sending no value: native deposits
+        //    come out of the signer's intent balance in the contract
+        // 3. Submit the transaction and wait for finalization
+        // 4. Return the transaction hash
+        self.mock_execute_intent(signer, intent, signature).await
+    }
+
+    /// Claim a signer for an intent about to be submitted
+    fn begin_relay(&self, signer: [u8; 20]) -> Result<()> {
+        if !self.in_flight.lock().unwrap().insert(signer) {
+            return Err(anyhow::anyhow!(
+                "An intent from {} is already being relayed",
+                Self::encode_address(&signer)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Release a signer once its intent has been submitted or rejected
+    fn end_relay(&self, signer: [u8; 20]) {
+        self.in_flight.lock().unwrap().remove(&signer);
+    }
+
+    /// Read the signer's next nonce from the contract
+    async fn get_intent_nonce(&self, signer: [u8; 20]) -> Result<u64> {
+        // In a real implementation, this would dry-run the contract's
+        // `get_intent_nonce(signer)` message and decode the returned u64
+        Ok(self.mock_intent_nonces.lock().unwrap().get(&signer).copied().unwrap_or(0))
+    }
+
+    /// Check an intent can still run and decode its signer and signature
+    fn validate_signed_intent(signed: &SignedIntent, now_ms: u64) -> Result<([u8; 20], [u8; 65])> {
+        let signer = Self::decode_address(&signed.signer)?;
+
+        if signed.intent.amount == 0 {
+            return Err(anyhow::anyhow!("Intent amount must be greater than 0"));
+        }
+
+        if signed.intent.deadline <= now_ms {
+            return Err(anyhow::anyhow!("Intent deadline has passed"));
+        }
+
+        Ok((signer, Self::decode_signature(&signed.signature)?))
+    }
+
+    pub fn decode_address(address: &str) -> Result<[u8; 20]> {
+        let hex = address
+            .strip_prefix("0x")
+            .ok_or_else(|| anyhow::anyhow!("Signer must be a 0x-prefixed H160 address"))?;
+        if hex.len() != 40 || !hex.is_ascii() {
+            return Err(anyhow::anyhow!("Signer must be 20 hex-encoded bytes"));
+        }
+
+        let mut bytes = [0u8; 20];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                .map_err(|e| anyhow::anyhow!("Invalid signer hex: {}", e))?;
+        }
+
+        Ok(bytes)
+    }
+
+    fn encode_address(address: &[u8; 20]) -> String {
+        address.iter().fold(String::from("0x"), |hex, byte| hex + &format!("{:02x}", byte))
+    }
+
+    pub fn decode_signature(signature: &str) -> Result<[u8; 65]> {
+        let hex = signature.strip_prefix("0x").unwrap_or(signature);
+        if hex.len() != 130 || !hex.is_ascii() {
+            return Err(anyhow::anyhow!("Signature must be 65 hex-encoded bytes"));
+        }
+
+        let mut bytes = [0u8; 65];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                .map_err(|e| anyhow::anyhow!("Invalid signature hex: {}", e))?;
+        }
+
+        Ok(bytes)
+    }
+
+    async fn mock_execute_intent(&self, signer: [u8; 20], intent: &Intent, _signature: [u8; 65]) -> Result<String> {
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        // The contract moves the signer to its next nonce once the intent runs
+        self.mock_intent_nonces.lock().unwrap().insert(signer, intent.nonce + 1);
+
+        // Return a mock transaction hash
+        Ok(format!(
+            "0x{:x}",
+            rand::random::<u64>()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNER: &str = "0xf24ff3a9cf04c71dbc94d0b566f7a27b94566cac";
+
+    fn signed_intent(nonce: u64, deadline: u64) -> SignedIntent {
+        SignedIntent {
+            signer: SIGNER.to_string(),
+            intent: Intent {
+                action: IntentAction::Invest,
+                strategy_id: 1,
+                amount: 1000000000000,
+                nonce,
+                deadline,
+            },
+            signature: format!("0x{}1b", "ab".repeat(64)),
+        }
+    }
+
+    #[test]
+    fn test_validate_signed_intent() {
+        let (signer, signature) = RelayerService::validate_signed_intent(&signed_intent(0, 2_000), 1_000).unwrap();
+        assert_eq!(RelayerService::encode_address(&signer), SIGNER);
+        assert_eq!(signature[0], 0xab);
+        assert_eq!(signature[64], 27);
+
+        assert!(RelayerService::validate_signed_intent(&signed_intent(0, 1_000), 1_000).is_err());
+
+        let mut zero_amount = signed_intent(0, 2_000);
+        zero_amount.intent.amount = 0;
+        assert!(RelayerService::validate_signed_intent(&zero_amount, 1_000).is_err());
+
+        let mut short_signature = signed_intent(0, 2_000);
+        short_signature.signature = "0xabcd".to_string();
+        assert!(RelayerService::validate_signed_intent(&short_signature, 1_000).is_err());
+
+        let mut ss58_signer = signed_intent(0, 2_000);
+        ss58_signer.signer = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string();
+        assert!(RelayerService::validate_signed_intent(&ss58_signer, 1_000).is_err());
+
+        let mut unprefixed_signer = signed_intent(0, 2_000);
+        unprefixed_signer.signer = SIGNER[2..].to_string();
+        assert!(RelayerService::validate_signed_intent(&unprefixed_signer, 1_000).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_intents_are_relayed_once() {
+        let relayer = RelayerService::new();
+        let deadline = chrono::Utc::now().timestamp_millis() as u64 + 60_000;
+
+        assert!(relayer.submit_intent(signed_intent(1, deadline)).await.is_err());
+        assert!(relayer.submit_intent(signed_intent(0, deadline)).await.is_ok());
+        assert!(relayer.submit_intent(signed_intent(0, deadline)).await.is_err());
+        assert!(relayer.submit_intent(signed_intent(2, deadline)).await.is_err());
+        assert!(relayer.submit_intent(signed_intent(1, deadline)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_nonces_come_from_the_contract() {
+        let relayer = RelayerService::new();
+        let signer = RelayerService::decode_address(SIGNER).unwrap();
+        let deadline = chrono::Utc::now().timestamp_millis() as u64 + 60_000;
+
+        // Intents the signer had relayed elsewhere already moved the contract's nonce
+        relayer.mock_intent_nonces.lock().unwrap().insert(signer, 5);
+        assert!(relayer.submit_intent(signed_intent(0, deadline)).await.is_err());
+        assert!(relayer.submit_intent(signed_intent(5, deadline)).await.is_ok());
+        assert_eq!(relayer.get_intent_nonce(signer).await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_one_intent_per_signer_in_flight() {
+        let relayer = RelayerService::new();
+        let signer = RelayerService::decode_address(SIGNER).unwrap();
+        let deadline = chrono::Utc::now().timestamp_millis() as u64 + 60_000;
+
+        relayer.begin_relay(signer).unwrap();
+        assert!(relayer.begin_relay(signer).is_err());
+        assert!(relayer.submit_intent(signed_intent(0, deadline)).await.is_err());
+
+        // A released signer can retry the nonce that was never submitted
+        relayer.end_relay(signer);
+        assert!(relayer.submit_intent(signed_intent(0, deadline)).await.is_ok());
+    }
+}
@@ -55,3 +55,24 @@ export type PieStrategy = {
 
 export type StrategyDetailsChartToggleOption = "APY" | "TVL" | "PRICE";
 export type InvestmentFormMode = "invest" | "withdraw" | "lp";
+
+/** 20-byte contract address, `0x`-prefixed hex */
+export type H160 = `0x${string}`;
+
+/**
+ * mirror `strategy_types` shared by the contracts, in the JSON shape of
+ * `polkadot::{Asset, Allocation, StrategyParameters}` in the backend;
+ * Asset SCALE-encodes as index 0 (Native) or 1 followed by the address (Psp22)
+ */
+export type Asset = "Native" | { Psp22: H160 };
+export type Allocation = {
+  asset: Asset;
+  /** basis points, all allocations sum to 10000 */
+  target_bps: number;
+};
+export type StrategyParameters = {
+  allocations: Allocation[];
+  max_slippage_bps: number;
+  rebalance_threshold_bps: number;
+  auto_compound: boolean;
+};